@@ -1,8 +1,27 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use regex::Regex;
 
 use std::path::PathBuf;
 
+use crate::filters::{parse_size_filter, parse_time_filter, SizeFilter, TimeFilter};
+
+#[derive(Clone, ValueEnum)]
+pub enum ColorMode {
+	Auto,
+	Always,
+	Never,
+}
+
+impl ColorMode {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Auto => "auto",
+			Self::Always => "always",
+			Self::Never => "never",
+		}
+	}
+}
+
 #[derive(Parser)]
 #[clap(version, about, author)]
 pub struct Arguments {
@@ -11,6 +30,10 @@ pub struct Arguments {
 	pub origin: PathBuf,
 	#[arg(short, long, help = "Walk recursively")]
 	pub recursive: bool,
+	#[arg(long = "max-depth", value_name = "N", help = "Limit recursion to N levels deep")]
+	pub max_depth: Option<usize>,
+	#[arg(long = "min-depth", value_name = "N", help = "Only show entries at least N levels deep")]
+	pub min_depth: Option<usize>,
 	#[arg(short, long, help = "Canonicalize display paths")]
 	pub canonicalize: bool,
 
@@ -21,21 +44,55 @@ pub struct Arguments {
 	pub directories: bool,
 
 	// Regex Config
-	#[arg(short, long, help = "Disable pattern case-sensitivity")]
+	#[arg(short, long, group = "case_config", help = "Disable pattern case-sensitivity")]
 	pub ignore_case: bool,
+	#[arg(short = 's', long, group = "case_config", help = "Forces pattern case-sensitivity, overriding smart-case")]
+	pub case_sensitive: bool,
 
 	// Pattern Matches
 	#[arg(short, long, help = "Match entries' name to pattern")]
 	pub name: Option<Regex>,
+	#[arg(short = 'p', long = "full-path", help = "Match --name against the entry's full path instead of just its file name")]
+	pub full_path: bool,
 	#[arg(short, long, groups = ["directories_have_no_text", "text_display_needs_newlines"], help = "Match entries' readable text to pattern")]
 	pub text: Option<Regex>,
+	#[arg(short = 'a', long, help = "Also search files that look binary")]
+	pub search_binary: bool,
+	#[arg(short = 'A', long = "after-context", value_name = "N", help = "Show N lines of context after each text match")]
+	pub after_context: Option<usize>,
+	#[arg(short = 'B', long = "before-context", value_name = "N", help = "Show N lines of context before each text match")]
+	pub before_context: Option<usize>,
+	#[arg(short = 'C', long = "context", value_name = "N", help = "Show N lines of context before and after each text match")]
+	pub context: Option<usize>,
 
 	// Display Options
 	#[arg(short, long, group = "text_display_needs_newlines", help = "Display entries in a non-line-breaking format")]
 	pub list: bool,
 	#[arg(short, long, help = "Includes relative working directory (\"./\") in entries' path display")]
 	pub working_dir: bool,
+	#[arg(long, value_enum, default_value = "auto", help = "Colorize output: auto, always, or never")]
+	pub color: ColorMode,
     
+	// Metadata Filters
+	#[arg(long, value_parser = parse_size_filter, allow_hyphen_values = true, help = "Filter by size, e.g. +10M, -1k, 500b")]
+	pub size: Option<SizeFilter>,
+	#[arg(long = "changed-within", value_parser = parse_time_filter, help = "Only match entries modified within a duration (2h, 3d) or since a UTC timestamp (YYYY-MM-DD[ HH:MM:SS])")]
+	pub changed_within: Option<TimeFilter>,
+	#[arg(long = "changed-before", value_parser = parse_time_filter, help = "Only match entries modified before a duration (2h, 3d) or a UTC timestamp (YYYY-MM-DD[ HH:MM:SS])")]
+	pub changed_before: Option<TimeFilter>,
+
+	// Ignore Rules
+	#[arg(short = 'H', long, help = "Include hidden entries")]
+	pub hidden: bool,
+	#[arg(short = 'I', long = "no-ignore", help = "Do not respect .gitignore/.ignore files")]
+	pub no_ignore: bool,
+
+	// Command Execution
+	#[arg(short = 'x', long = "exec", group = "exec_config", num_args = 1.., allow_hyphen_values = true, value_name = "CMD", help = "Execute a command for each matched entry")]
+	pub exec: Option<Vec<String>>,
+	#[arg(short = 'X', long = "exec-batch", group = "exec_config", num_args = 1.., allow_hyphen_values = true, value_name = "CMD", help = "Execute a command once with all matched entries")]
+	pub exec_batch: Option<Vec<String>>,
+
 	// Debug Flags
 	#[arg(long, help = "Enables debug warnings")]
 	pub debug: bool
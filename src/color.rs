@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+// Mirrors the file-type selectors of GNU coreutils' default `dircolors` output
+// (without its `*.ext` rules), used whenever `LS_COLORS` isn't set in the
+// environment.
+const DEFAULT_LS_COLORS: &str = "di=01;34:ln=01;36:mh=00:pi=40;33:so=01;35:do=01;35:\
+bd=40;33;01:cd=40;33;01:or=40;31;01:mi=00:su=37;41:sg=30;43:ca=30;41:tw=30;42:ow=34;42:\
+st=37;44:ex=01;32";
+
+const MATCH_HIGHLIGHT: &str = "32;1;4";
+
+fn parse_ls_colors(raw: &str) -> HashMap<String, String> {
+	raw.split(':')
+		.filter_map(|entry| entry.split_once('='))
+		.map(|(selector, code)| (selector.to_string(), code.to_string()))
+		.collect()
+}
+
+/// Resolves `LS_COLORS` selectors to ANSI SGR codes and paints path
+/// components accordingly, gated behind a tty check plus `--color`.
+pub struct Painter {
+	enabled: bool,
+	styles: HashMap<String, String>,
+}
+
+impl Painter {
+	pub fn new(color_mode: &str) -> Self {
+		let enabled = match color_mode {
+			"always" => true,
+			"never" => false,
+			_ => std::io::stdout().is_terminal(),
+		};
+		let raw = std::env::var("LS_COLORS").unwrap_or_else(|_| DEFAULT_LS_COLORS.to_string());
+		Self { enabled, styles: parse_ls_colors(&raw) }
+	}
+
+	fn style_for(&self, path: &Path) -> Option<&str> {
+		let metadata = std::fs::symlink_metadata(path).ok()?;
+		let file_type = metadata.file_type();
+
+		if file_type.is_symlink() {
+			let selector = if std::fs::metadata(path).is_ok() { "ln" } else { "or" };
+			return self.styles.get(selector).map(String::as_str);
+		}
+		if file_type.is_dir() {
+			return self.styles.get("di").map(String::as_str);
+		}
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::PermissionsExt;
+			if metadata.permissions().mode() & 0o111 != 0 {
+				if let Some(style) = self.styles.get("ex") { return Some(style.as_str()) }
+			}
+		}
+		if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+			if let Some(style) = self.styles.get(&format!("*.{extension}")) { return Some(style.as_str()) }
+		}
+		self.styles.get("fi").map(String::as_str)
+	}
+
+	// Wraps `text` in the type-sensitive style resolved from `path`'s own
+	// `symlink_metadata`, leaving it unstyled if coloring is disabled or no
+	// selector matches.
+	pub fn paint_path(&self, text: &str, path: &Path) -> String {
+		if !self.enabled { return text.to_string() }
+		match self.style_for(path) {
+			Some(style) => format!("\x1b[{style}m{text}\x1b[0m"),
+			None => text.to_string(),
+		}
+	}
+
+	// Wraps `text` in the match-highlight style, layered on top of `path`'s
+	// base style so the highlight's attributes (which are listed last) win.
+	pub fn paint_highlight(&self, text: &str, path: &Path) -> String {
+		if !self.enabled { return text.to_string() }
+		match self.style_for(path) {
+			Some(base_style) => format!("\x1b[{base_style};{MATCH_HIGHLIGHT}m{text}\x1b[0m"),
+			None => format!("\x1b[{MATCH_HIGHLIGHT}m{text}\x1b[0m"),
+		}
+	}
+
+	// Paints each component of `parent_path` individually, since a symlinked
+	// directory partway down should get its own style rather than inheriting
+	// the final entry's.
+	pub fn paint_parent(&self, parent_path: &Path) -> String {
+		use std::path::Component;
+
+		let mut painted = String::new();
+		let mut cumulative = PathBuf::new();
+		for component in parent_path.components() {
+			cumulative.push(component.as_os_str());
+			painted += &self.paint_path(&component.as_os_str().to_string_lossy(), &cumulative);
+			// The root component's own text already ends in a separator (e.g. "/"),
+			// so only non-root components need one appended after them.
+			if !matches!(component, Component::RootDir | Component::Prefix(_)) {
+				painted += "/";
+			}
+		}
+		painted
+	}
+
+	// Dims and italicizes the non-matched remainder of a `--text` line.
+	pub fn paint_context(&self, text: &str) -> String {
+		if !self.enabled { return text.to_string() }
+		format!("\x1b[2;3m{text}\x1b[0m")
+	}
+
+	// Bolds a `--text` line number prefix.
+	pub fn paint_bold(&self, text: &str) -> String {
+		if !self.enabled { return text.to_string() }
+		format!("\x1b[1m{text}\x1b[0m")
+	}
+
+	// Highlights a matched `--text` span, with no path-derived base style.
+	pub fn paint_match(&self, text: &str) -> String {
+		if !self.enabled { return text.to_string() }
+		format!("\x1b[{MATCH_HIGHLIGHT}m{text}\x1b[0m")
+	}
+}
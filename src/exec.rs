@@ -0,0 +1,147 @@
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+/// A command template parsed from the trailing `-x`/`-X` tokens, with
+/// placeholders substituted against a matched path before spawning.
+pub struct CommandTemplate {
+	tokens: Vec<String>,
+}
+
+impl CommandTemplate {
+	pub fn new(tokens: Vec<String>) -> Self {
+		Self { tokens }
+	}
+
+	// Strips a path's extension, mirroring `Path::file_stem`/`with_extension`
+	// but operating on an already-stringified path so `{.}`/`{/.}` can reuse it.
+	fn strip_extension(path_str: &str) -> String {
+		match path_str.rfind('.') {
+			Some(dot_index) if dot_index > path_str.rfind('/').map(|i| i + 1).unwrap_or(0) => {
+				path_str[..dot_index].to_string()
+			}
+			_ => path_str.to_string(),
+		}
+	}
+
+	fn substitute(token: &str, path: &Path) -> String {
+		if !token.contains('{') { return token.to_string() }
+
+		let full_path = path.to_string_lossy().to_string();
+		let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+		let parent = path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+		let stem_path = Self::strip_extension(&full_path);
+		let stem_name = Self::strip_extension(&file_name);
+
+		token
+			.replace("{/.}", &stem_name)
+			.replace("{//}", &parent)
+			.replace("{.}", &stem_path)
+			.replace("{/}", &file_name)
+			.replace("{}", &full_path)
+	}
+
+	// Builds the argv for a single match, appending the full path as a final
+	// argument when no placeholder appeared anywhere in the template.
+	fn build_args(&self, path: &Path) -> Vec<String> {
+		let has_placeholder = self.tokens.iter().any(|token| {
+			["{}", "{/}", "{//}", "{.}", "{/.}"].iter().any(|placeholder| token.contains(placeholder))
+		});
+		let mut substituted: Vec<String> = self.tokens.iter().map(|token| Self::substitute(token, path)).collect();
+		if !has_placeholder {
+			substituted.push(path.to_string_lossy().to_string());
+		}
+		substituted
+	}
+
+	// Builds the argv for `--exec-batch`: a bare `{}` token expands into every
+	// matched path, while any other token is passed through unchanged.
+	fn build_batch_args(&self, paths: &[String]) -> Vec<String> {
+		let has_placeholder = self.tokens.iter().any(|token| token == "{}");
+		let mut batch_args: Vec<String> = vec![];
+		for token in &self.tokens {
+			if token == "{}" {
+				batch_args.extend(paths.iter().cloned());
+			} else {
+				batch_args.push(token.clone());
+			}
+		}
+		if !has_placeholder {
+			batch_args.extend(paths.iter().cloned());
+		}
+		batch_args
+	}
+
+	fn run(args: &[String]) -> i32 {
+		let Some((program, rest)) = args.split_first() else { return 0 };
+		match Command::new(program).args(rest).status() {
+			Ok(status) => status.code().unwrap_or(1),
+			Err(_) => 1,
+		}
+	}
+
+	// Runs a command with its stdout/stderr captured rather than inherited, so
+	// concurrent children from `run_for_each` can't interleave partial lines.
+	fn run_captured(args: &[String]) -> (i32, Vec<u8>, Vec<u8>) {
+		let Some((program, rest)) = args.split_first() else { return (0, vec![], vec![]) };
+		match Command::new(program).args(rest).output() {
+			Ok(output) => (output.status.code().unwrap_or(1), output.stdout, output.stderr),
+			Err(_) => (1, vec![], vec![]),
+		}
+	}
+
+	/// Spawns one child process per path, bounded by a thread pool sized to
+	/// the available CPU count. Each child's output is captured and replayed
+	/// in match order once every job has finished, and the worst (first
+	/// non-zero) exit code encountered is returned.
+	pub fn run_for_each(&self, paths: Vec<std::path::PathBuf>) -> i32 {
+		let worker_count = thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(paths.len().max(1));
+
+		let (job_tx, job_rx) = mpsc::channel::<(usize, std::path::PathBuf)>();
+		let job_rx = std::sync::Arc::new(std::sync::Mutex::new(job_rx));
+		let (result_tx, result_rx) = mpsc::channel::<(usize, i32, Vec<u8>, Vec<u8>)>();
+
+		for (job_index, path) in paths.iter().cloned().enumerate() {
+			job_tx.send((job_index, path)).ok();
+		}
+		drop(job_tx);
+
+		thread::scope(|scope| {
+			for _ in 0..worker_count {
+				let job_rx = std::sync::Arc::clone(&job_rx);
+				let result_tx = result_tx.clone();
+				scope.spawn(move || {
+					loop {
+						let job = job_rx.lock().unwrap().recv();
+						let Ok((job_index, path)) = job else { break };
+						let (exit_code, stdout, stderr) = Self::run_captured(&self.build_args(&path));
+						result_tx.send((job_index, exit_code, stdout, stderr)).ok();
+					}
+				});
+			}
+			drop(result_tx);
+		});
+
+		let mut results: Vec<Option<(i32, Vec<u8>, Vec<u8>)>> = (0..paths.len()).map(|_| None).collect();
+		for (job_index, exit_code, stdout, stderr) in result_rx {
+			results[job_index] = Some((exit_code, stdout, stderr));
+		}
+
+		use std::io::Write;
+		let mut exit_code = 0;
+		for result in results.into_iter().flatten() {
+			let (job_exit_code, stdout, stderr) = result;
+			std::io::stdout().write_all(&stdout).ok();
+			std::io::stderr().write_all(&stderr).ok();
+			if job_exit_code != 0 && exit_code == 0 { exit_code = job_exit_code }
+		}
+		exit_code
+	}
+
+	/// Invokes the command once with every matched path substituted into it.
+	pub fn run_batch(&self, paths: Vec<std::path::PathBuf>) -> i32 {
+		let path_strings: Vec<String> = paths.iter().map(|path| path.to_string_lossy().to_string()).collect();
+		Self::run(&self.build_batch_args(&path_strings))
+	}
+}
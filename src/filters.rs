@@ -0,0 +1,120 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A parsed `--size` expression: the leading sign selects "at least"/"at
+/// most", an unsigned expression means "exactly".
+#[derive(Clone)]
+pub enum SizeFilter {
+	Min(u64),
+	Max(u64),
+	Equals(u64),
+}
+
+impl SizeFilter {
+	pub fn matches(&self, byte_len: u64) -> bool {
+		match self {
+			Self::Min(min_bytes) => byte_len >= *min_bytes,
+			Self::Max(max_bytes) => byte_len <= *max_bytes,
+			Self::Equals(exact_bytes) => byte_len == *exact_bytes,
+		}
+	}
+}
+
+/// A parsed `--changed-within`/`--changed-before` expression, resolved to an
+/// absolute cutoff at parse time.
+#[derive(Clone)]
+pub struct TimeFilter(pub SystemTime);
+
+pub fn parse_size_filter(raw: &str) -> Result<SizeFilter, String> {
+	let (sign, rest) = match raw.as_bytes().first() {
+		Some(b'+') => (Some('+'), &raw[1..]),
+		Some(b'-') => (Some('-'), &raw[1..]),
+		_ => (None, raw),
+	};
+	let byte_count = parse_byte_count(rest)?;
+	Ok(match sign {
+		Some('+') => SizeFilter::Min(byte_count),
+		Some('-') => SizeFilter::Max(byte_count),
+		_ => SizeFilter::Equals(byte_count),
+	})
+}
+
+fn parse_byte_count(raw: &str) -> Result<u64, String> {
+	let split_index = raw.find(|digit_char: char| !digit_char.is_ascii_digit()).unwrap_or(raw.len());
+	let (number_str, unit_str) = raw.split_at(split_index);
+	let number: u64 = number_str.parse().map_err(|_| format!("Invalid size \"{raw}\""))?;
+	let multiplier: u64 = match unit_str.to_lowercase().as_str() {
+		"" | "b" => 1,
+		"k" => 1_000,
+		"ki" => 1024,
+		"m" => 1_000_000,
+		"mi" => 1024 * 1024,
+		"g" => 1_000_000_000,
+		"gi" => 1024 * 1024 * 1024,
+		"t" => 1_000_000_000_000,
+		"ti" => 1024u64.pow(4),
+		other => return Err(format!("Unknown size unit \"{other}\"")),
+	};
+	Ok(number * multiplier)
+}
+
+pub fn parse_time_filter(raw: &str) -> Result<TimeFilter, String> {
+	if let Some(duration) = parse_relative_duration(raw) {
+		return SystemTime::now()
+			.checked_sub(duration)
+			.map(TimeFilter)
+			.ok_or_else(|| format!("Duration \"{raw}\" is too far in the past"));
+	}
+	parse_absolute_timestamp(raw).map(TimeFilter)
+}
+
+fn parse_relative_duration(raw: &str) -> Option<Duration> {
+	let split_index = raw.find(|digit_char: char| !digit_char.is_ascii_digit())?;
+	if split_index == 0 { return None }
+	let (number_str, unit_str) = raw.split_at(split_index);
+	let number: u64 = number_str.parse().ok()?;
+	let seconds_per_unit = match unit_str.to_lowercase().as_str() {
+		"s" | "sec" | "secs" | "second" | "seconds" => 1,
+		"m" | "min" | "mins" | "minute" | "minutes" => 60,
+		"h" | "hour" | "hours" => 3600,
+		"d" | "day" | "days" => 86400,
+		"w" | "week" | "weeks" => 86400 * 7,
+		_ => return None,
+	};
+	Some(Duration::from_secs(number * seconds_per_unit))
+}
+
+// Interprets `YYYY-MM-DD[ HH:MM:SS]` as UTC, not the user's local time zone,
+// since the stdlib has no time zone database to convert against.
+fn parse_absolute_timestamp(raw: &str) -> Result<SystemTime, String> {
+	let mut halves = raw.splitn(2, ' ');
+	let date_part = halves.next().unwrap_or("");
+	let time_part = halves.next().unwrap_or("00:00:00");
+
+	let mut date_fields = date_part.splitn(3, '-');
+	let year: i64 = date_fields.next().and_then(|field| field.parse().ok()).ok_or_else(|| format!("Invalid date \"{raw}\""))?;
+	let month: u32 = date_fields.next().and_then(|field| field.parse().ok()).ok_or_else(|| format!("Invalid date \"{raw}\""))?;
+	let day: u32 = date_fields.next().and_then(|field| field.parse().ok()).ok_or_else(|| format!("Invalid date \"{raw}\""))?;
+
+	let mut time_fields = time_part.splitn(3, ':');
+	let hour: i64 = time_fields.next().unwrap_or("0").parse().map_err(|_| format!("Invalid time \"{raw}\""))?;
+	let minute: i64 = time_fields.next().unwrap_or("0").parse().map_err(|_| format!("Invalid time \"{raw}\""))?;
+	let second: i64 = time_fields.next().unwrap_or("0").parse().map_err(|_| format!("Invalid time \"{raw}\""))?;
+
+	let total_seconds = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+	if total_seconds < 0 {
+		return Err(format!("Timestamp \"{raw}\" is before the Unix epoch"));
+	}
+	Ok(UNIX_EPOCH + Duration::from_secs(total_seconds as u64))
+}
+
+// Howard Hinnant's days-from-civil algorithm: days since 1970-01-01 (UTC) for
+// a given proleptic-Gregorian year/month/day, used in lieu of a date-time crate.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = if year >= 0 { year } else { year - 399 } / 400;
+	let year_of_era = year - era * 400;
+	let month_index = (month as i64 + 9) % 12;
+	let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146097 + day_of_era - 719468
+}
@@ -0,0 +1,143 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+struct IgnoreRule {
+	regex: Regex,
+	negated: bool,
+	directory_only: bool,
+}
+
+impl IgnoreRule {
+	fn parse(line: &str) -> Option<Self> {
+		let line = line.trim_end();
+		if line.is_empty() || line.starts_with('#') { return None }
+
+		let mut pattern = line;
+		let negated = pattern.starts_with('!');
+		if negated { pattern = &pattern[1..] }
+
+		let directory_only = pattern.ends_with('/');
+		if directory_only { pattern = &pattern[..pattern.len() - 1] }
+
+		// Anchored if the pattern has a `/` anywhere but the (already-stripped) end.
+		let anchored = pattern.contains('/');
+		let glob = pattern.trim_start_matches('/');
+
+		Some(Self { regex: Regex::new(&Self::glob_to_regex(glob, anchored)).ok()?, negated, directory_only })
+	}
+
+	// Translates a single gitignore glob into an anchored regex: `**` crosses
+	// directory separators, `*`/`?` do not, and floating patterns (no `/` other
+	// than a trailing one) are allowed to match starting at any path component.
+	fn glob_to_regex(glob: &str, anchored: bool) -> String {
+		let mut regex_str = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+		let mut chars = glob.chars().peekable();
+		while let Some(current_char) = chars.next() {
+			match current_char {
+				'*' if chars.peek() == Some(&'*') => {
+					chars.next();
+					if chars.peek() == Some(&'/') {
+						chars.next();
+						regex_str += "(?:.*/)?";
+					} else {
+						regex_str += ".*";
+					}
+				}
+				'*' => regex_str += "[^/]*",
+				'?' => regex_str += "[^/]",
+				'[' => {
+					regex_str += "[";
+					for class_char in chars.by_ref() {
+						if class_char == ']' { break }
+						regex_str.push(class_char);
+					}
+					regex_str += "]";
+				}
+				other => regex_str += &regex::escape(&other.to_string()),
+			}
+		}
+		regex_str += "$";
+		regex_str
+	}
+}
+
+/// Resolves `.gitignore`/`.ignore` rules per entry from its ancestor chain,
+/// since jwalk's parallel directory reads rule out a single global rule
+/// stack that's pushed/popped as a sequential walk descends.
+pub struct IgnoreMatcher {
+	origin: PathBuf,
+	rules_by_dir: HashMap<PathBuf, Rc<Vec<IgnoreRule>>>,
+	ignored_by_path: HashMap<PathBuf, bool>,
+}
+
+impl IgnoreMatcher {
+	pub fn new(origin: &Path) -> Self {
+		Self { origin: origin.to_path_buf(), rules_by_dir: HashMap::new(), ignored_by_path: HashMap::new() }
+	}
+
+	fn rules_in(&mut self, dir: &Path) -> Rc<Vec<IgnoreRule>> {
+		if let Some(rules) = self.rules_by_dir.get(dir) { return Rc::clone(rules) }
+
+		let mut rules = vec![];
+		for ignore_file_name in IGNORE_FILE_NAMES {
+			if let Ok(contents) = fs::read_to_string(dir.join(ignore_file_name)) {
+				rules.extend(contents.lines().filter_map(IgnoreRule::parse));
+			}
+		}
+		let rules = Rc::new(rules);
+		self.rules_by_dir.insert(dir.to_path_buf(), Rc::clone(&rules));
+		rules
+	}
+
+	// Tests `path` against the rules defined in each of its ancestor
+	// directories (from the walk origin down to its immediate parent),
+	// deepest ancestor first, honoring negation with first-match-wins.
+	fn matches_own_rules(&mut self, path: &Path, is_dir: bool) -> bool {
+		let mut ancestors = vec![];
+		let mut current = path.parent();
+		while let Some(dir) = current {
+			ancestors.push(dir.to_path_buf());
+			if dir == self.origin { break }
+			current = dir.parent();
+		}
+
+		for ancestor_dir in ancestors {
+			let relative_path = path.strip_prefix(&ancestor_dir).unwrap_or(path);
+			let relative_str = relative_path.to_string_lossy();
+			for rule in self.rules_in(&ancestor_dir).iter().rev() {
+				if rule.directory_only && !is_dir { continue }
+				if rule.regex.is_match(&relative_str) { return !rule.negated }
+			}
+		}
+		false
+	}
+
+	/// Returns true if `path` should be excluded, either because it matches
+	/// an ignore rule itself or because an ancestor directory does.
+	pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+		if path == self.origin { return false }
+		if let Some(&cached) = self.ignored_by_path.get(path) { return cached }
+
+		let parent_ignored = match path.parent() {
+			Some(parent_dir) if parent_dir != path => self.is_ignored(parent_dir, true),
+			_ => false,
+		};
+		let ignored = parent_ignored || self.matches_own_rules(path, is_dir);
+		self.ignored_by_path.insert(path.to_path_buf(), ignored);
+		ignored
+	}
+}
+
+/// Returns true if any component between the walk origin and `path` starts
+/// with a `.`, so an entry nested under a hidden directory is caught even
+/// when its own name isn't hidden.
+pub fn is_hidden(origin: &Path, path: &Path) -> bool {
+	path.strip_prefix(origin).unwrap_or(path).components().any(|component| {
+		component.as_os_str().to_str().map(|name| name.starts_with('.')).unwrap_or(false)
+	})
+}
@@ -5,10 +5,53 @@ use jwalk::WalkDir;
 use colored::Colorize;
 
 // Standard Imports
-use std::fs::read;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
 // Internal Imports
 mod args;
+mod color;
+mod exec;
+mod filters;
+mod ignore;
+
+// Returns true if `pattern` contains a literal uppercase character, ignoring
+// escape sequences (`\X`) and escaped constructs (`\p{Lu}`, `\P{...}`) so that
+// e.g. `\D` does not wrongly trigger case-sensitivity.
+fn pattern_has_uppercase_char(pattern: &str) -> bool {
+	let mut chars = pattern.chars().peekable();
+	while let Some(current_char) = chars.next() {
+		if current_char == '\\' {
+			match chars.next() {
+				Some('p') | Some('P') => {
+					if chars.peek() == Some(&'{') {
+						for inner_char in chars.by_ref() {
+							if inner_char == '}' { break }
+						}
+					} else {
+						// Single-letter class shorthand, e.g. `\pL`/`\PN`
+						chars.next();
+					}
+				}
+				_ => {}
+			}
+			continue;
+		}
+		if current_char.is_uppercase() { return true }
+	}
+	false
+}
+
+// Formats a context line (non-matched, surrounding an actual match) dimmed
+// and italic with its 1-based line number, for `-A`/`-B`/`-C`.
+fn format_context_line(painter: &color::Painter, line_index: usize, line_bytes: &[u8]) -> String {
+	format!(
+		"\t{}{} {}",
+		painter.paint_context(&(line_index + 1).to_string()),
+		painter.paint_context(":"),
+		painter.paint_context(&String::from_utf8_lossy(line_bytes))
+	)
+}
 
 fn main() {
 	// Args. Parse
@@ -47,41 +90,138 @@ fn main() {
 			Ok(canon_path) => canon_path
 		};
 	}
-	if args.ignore_case {
+	// Smart case is on by default: a pattern is only made case-insensitive when it
+	// contains no literal uppercase characters. `--ignore-case` forces insensitivity
+	// regardless of case, while `--case-sensitive` disables smart-case entirely.
+	let smart_case = !args.ignore_case && !args.case_sensitive;
+	if args.ignore_case || smart_case {
 		if let Some(name_pattern) = args.name {
-			args.name = match Regex::new(format!("(?i){}", name_pattern.as_str()).as_str()) {
-				Err(_) => {fail!("Could not make name pattern case-insensitive");},
-				Ok(modified_pattern) => Some(modified_pattern),
+			args.name = if args.ignore_case || !pattern_has_uppercase_char(name_pattern.as_str()) {
+				match Regex::new(format!("(?i){}", name_pattern.as_str()).as_str()) {
+					Err(_) => {fail!("Could not make name pattern case-insensitive");},
+					Ok(modified_pattern) => Some(modified_pattern),
+				}
+			} else {
+				Some(name_pattern)
 			}
 		}
 		if let Some(text_pattern) = args.text {
-			args.text = match Regex::new(format!("(?i){}", text_pattern.as_str()).as_str()) {
-				Err(_) => {fail!("Could not make text pattern case-insensitive");},
-				Ok(modified_pattern) => Some(modified_pattern),
+			args.text = if args.ignore_case || !pattern_has_uppercase_char(text_pattern.as_str()) {
+				match Regex::new(format!("(?i){}", text_pattern.as_str()).as_str()) {
+					Err(_) => {fail!("Could not make text pattern case-insensitive");},
+					Ok(modified_pattern) => Some(modified_pattern),
+				}
+			} else {
+				Some(text_pattern)
 			}
 		}
 	}
-	
+
 	// Walker construction
 	let mut walker = WalkDir::new(&args.origin).skip_hidden(false);
-	if !args.recursive { walker = walker.max_depth(1) }
+	if let Some(max_depth) = args.max_depth {
+		walker = walker.max_depth(max_depth);
+	} else if !args.recursive {
+		walker = walker.max_depth(1);
+	}
 
 	// Entry walk
 	let mut entries_list: Vec<String> = vec![];
+	let mut matched_paths: Vec<std::path::PathBuf> = vec![];
+	let exec_active = args.exec.is_some() || args.exec_batch.is_some();
+	let mut ignore_matcher = ignore::IgnoreMatcher::new(&args.origin);
+	let painter = color::Painter::new(args.color.as_str());
+	let text_bytes_pattern = match &args.text {
+		None => None,
+		Some(text_pattern) => match regex::bytes::Regex::new(text_pattern.as_str()) {
+			Err(_) => {fail!("Could not compile text pattern");},
+			Ok(bytes_pattern) => Some(bytes_pattern),
+		}
+	};
 	for dir_entry_result in walker {
 		if let Ok(dir_entry) = dir_entry_result {
 			let entry_path = dir_entry.path();
 
 			// Type filters evaluation
 			if args.origin.is_dir() && entry_path == args.origin { continue }
+			if let Some(min_depth) = args.min_depth {
+				if dir_entry.depth() < min_depth { continue }
+			}
 			if !(!args.directories && !args.files) {
 				if entry_path.is_file() && !args.files { continue }
 				if entry_path.is_dir() && !args.directories { continue }
 			}
 
+			// Hidden / ignore-file filtering
+			if !args.hidden && ignore::is_hidden(&args.origin, &entry_path) { continue }
+			if !args.no_ignore && ignore_matcher.is_ignored(&entry_path, entry_path.is_dir()) { continue }
+
+			// Metadata filters evaluation
+			if args.size.is_some() || args.changed_within.is_some() || args.changed_before.is_some() {
+				match entry_path.metadata() {
+					Err(_) => {entry_warn!(entry_path, "Could not stat");},
+					Ok(entry_metadata) => {
+						if let Some(size_filter) = &args.size {
+							if !entry_metadata.is_dir() && !size_filter.matches(entry_metadata.len()) { continue }
+						}
+						if args.changed_within.is_some() || args.changed_before.is_some() {
+							let modified = match entry_metadata.modified() {
+								Err(_) => {entry_warn!(entry_path, "Could not read modification time");},
+								Ok(modified) => modified,
+							};
+							if let Some(filters::TimeFilter(cutoff)) = &args.changed_within {
+								if modified < *cutoff { continue }
+							}
+							if let Some(filters::TimeFilter(cutoff)) = &args.changed_before {
+								if modified > *cutoff { continue }
+							}
+						}
+					}
+				}
+			}
+
 			// Name matcher evaluation
 			let display_path = match &args.name {
-				None => entry_path.to_string_lossy().to_string(),
+				None => {
+					let mut display_path_buf = String::new();
+					if let Some(parent_path) = entry_path.parent() {
+						display_path_buf += &painter.paint_parent(parent_path);
+					}
+					if let Some(file_name) = entry_path.file_name() {
+						display_path_buf += &painter.paint_path(&file_name.to_string_lossy(), &entry_path);
+					}
+					display_path_buf
+				},
+				Some(name_pattern) if args.full_path => {
+					// Full path retrieval, since the pattern is matched across directory separators
+					let full_path_str = match entry_path.to_str() {
+						None => {entry_warn!(entry_path, "Could not interpret path");},
+						Some(full_path_str) => full_path_str
+					};
+
+					// Captures iteration
+					let mut display_path_buf = String::new();
+					let mut first_capture = true;
+					let mut last_index = 0;
+					for capture in name_pattern.captures_iter(full_path_str) {
+						first_capture = false;
+
+						// Captured path span push to display path buffer, which may cross "/"
+						let first_capture = capture.get(0).unwrap();
+						let start = first_capture.start();
+						let end = first_capture.end();
+						display_path_buf += &painter.paint_path(full_path_str.get(last_index..start).unwrap(), &entry_path);
+						display_path_buf += &painter.paint_highlight(full_path_str.get(start..end).unwrap(), &entry_path);
+						last_index = end;
+					}
+
+					// Entry iterator continuation upon no captures found
+					if first_capture { continue }
+					// Display path buffer with remaining slice return
+					else {
+						display_path_buf + &painter.paint_path(full_path_str.get(last_index..).unwrap(), &entry_path)
+					}
+				}
 				Some(name_pattern) => {
 					// File name retrieval
 					let entry_name = match entry_path.file_name() {
@@ -91,7 +231,7 @@ fn main() {
 							Some(file_name_str) => file_name_str
 						}
 					};
-					
+
 					// Captures iteration
 					let mut display_path_buf = String::new();
 					let mut first_capture = true;
@@ -101,79 +241,121 @@ fn main() {
 						if first_capture {
 							first_capture = false;
 							if let Some(parent_path) = entry_path.parent() {
-								display_path_buf += &parent_path.to_string_lossy();
-								display_path_buf += "/";
+								display_path_buf += &painter.paint_parent(parent_path);
 							}
 						}
-						
+
 						// Captured entry name push to display path buffer
 						let first_capture = capture.get(0).unwrap();
 						let start = first_capture.start();
 						let end = first_capture.end();
-						display_path_buf += entry_name.get(last_index..start).unwrap();
-						display_path_buf += &entry_name.get(start..end).unwrap().green().bold().underline().to_string();
+						display_path_buf += &painter.paint_path(entry_name.get(last_index..start).unwrap(), &entry_path);
+						display_path_buf += &painter.paint_highlight(entry_name.get(start..end).unwrap(), &entry_path);
 						last_index = end;
 					}
 
 					// Entry iterator continuation upon no captures found
 					if first_capture { continue }
-					// Display path buffer with remaining slice return 
+					// Display path buffer with remaining slice return
 					else {
-						display_path_buf + entry_name.get(last_index..).unwrap()
+						display_path_buf + &painter.paint_path(entry_name.get(last_index..).unwrap(), &entry_path)
 					}
 				}
 			};
 
 			// Text matcher evaluation
-			let display_text_lines = match &args.text {
+			let display_text_lines = match &text_bytes_pattern {
 				None => vec![],
 				Some(text_pattern) => {
 					// Directory skip
 					if entry_path.is_dir() { continue }
 
-					// Entry text lines iteration
+					let mut reader = match File::open(&entry_path) {
+						Err(_) => {entry_warn!(entry_path, "Could not open");},
+						Ok(file) => BufReader::new(file),
+					};
+
+					// Binary detection over the buffered prefix, unless overridden
+					if !args.search_binary {
+						match reader.fill_buf() {
+							Err(_) => {entry_warn!(entry_path, "Could not read");},
+							Ok(prefix_bytes) => if prefix_bytes.contains(&0u8) { continue },
+						}
+					}
+
+					// Context window sizes: `-C` sets both sides unless overridden individually
+					let before_context = args.before_context.or(args.context).unwrap_or(0);
+					let after_context = args.after_context.or(args.context).unwrap_or(0);
+
+					// Entry text lines iteration, streamed to bound memory on large files
 					let mut display_text_lines_buf: Vec<String> = vec![];
 					let mut line_matched_flag = false;
-					for (line_index, line) in
-					match String::from_utf8(
-						match read(entry_path.clone()) {
+					let mut line_bytes: Vec<u8> = vec![];
+					let mut line_index = 0usize;
+					let mut before_buffer: std::collections::VecDeque<(usize, Vec<u8>)> = std::collections::VecDeque::with_capacity(before_context);
+					let mut after_remaining = 0usize;
+					let mut last_emitted_line: Option<usize> = None;
+					loop {
+						line_bytes.clear();
+						let bytes_read = match reader.read_until(b'\n', &mut line_bytes) {
 							Err(_) => {entry_warn!(entry_path, "Could not read");},
-							Ok(read_bytes) => read_bytes
-						}
-					) {
-						Err(_) => {entry_warn!(entry_path, "Could not decode");},
-						Ok(read_string) => read_string
-					}.to_string().lines().enumerate() {
+							Ok(bytes_read) => bytes_read,
+						};
+						if bytes_read == 0 { break }
+						if line_bytes.last() == Some(&b'\n') { line_bytes.pop(); }
+						if line_bytes.last() == Some(&b'\r') { line_bytes.pop(); }
+
 						// Captures iteration
 						let mut display_line_buf = String::new();
 						let mut first_capture = true;
 						let mut last_index = 0;
-						for capture in text_pattern.captures_iter(line) {
-							if !line_matched_flag { line_matched_flag = true }
-
+						for capture in text_pattern.captures_iter(&line_bytes) {
 							// Text push from start of line to start of capture to display line buffer upon first iteration
 							if first_capture {
 								first_capture = false;
 								display_line_buf += "\t";
-								display_line_buf += &(line_index + 1).to_string().bold().to_string();
-								display_line_buf += &": ".bold().to_string();
+								display_line_buf += &painter.paint_bold(&(line_index + 1).to_string());
+								display_line_buf += &painter.paint_bold(": ");
 							}
 
-							// Captured text push to display line buffer
+							// Captured text push to display line buffer, decoding losslessly since
+							// a valid-UTF-8 match may sit inside an otherwise non-UTF-8 line
 							let first_capture = capture.get(0).unwrap();
 							let start = first_capture.start();
 							let end = first_capture.end();
-							display_line_buf += &line.get(last_index..start).unwrap().dimmed().italic().to_string();
-							display_line_buf += &line.get(start..end).unwrap().green().bold().underline().to_string();
+							display_line_buf += &painter.paint_context(&String::from_utf8_lossy(&line_bytes[last_index..start]));
+							display_line_buf += &painter.paint_match(&String::from_utf8_lossy(&line_bytes[start..end]));
 							last_index = end;
 						}
 
-						// Line iterator continuation upon no inner-line captures found
-						if first_capture { continue }
-						// Display line buffer with remaining slice push into display text lines buffer
-						else { 
-							display_text_lines_buf.push(display_line_buf + &line.get(last_index..).unwrap().dimmed().italic().to_string())
+						if !first_capture {
+							line_matched_flag = true;
+							display_line_buf += &painter.paint_context(&String::from_utf8_lossy(&line_bytes[last_index..]));
+
+							// Flush buffered before-context, inserting a separator if it doesn't
+							// continue on from whatever was last emitted (match or after-context)
+							let first_buffered_line = before_buffer.front().map(|(context_line_index, _)| *context_line_index).unwrap_or(line_index);
+							let context_active = before_context > 0 || after_context > 0;
+							if context_active && last_emitted_line.is_some_and(|prev_line| first_buffered_line > prev_line + 1) {
+								display_text_lines_buf.push(painter.paint_context("--"));
+							}
+							for (context_line_index, context_line_bytes) in before_buffer.drain(..) {
+								display_text_lines_buf.push(format_context_line(&painter, context_line_index, &context_line_bytes));
+							}
+
+							display_text_lines_buf.push(display_line_buf);
+							last_emitted_line = Some(line_index);
+							after_remaining = after_context;
+						} else if after_remaining > 0 {
+							display_text_lines_buf.push(format_context_line(&painter, line_index, &line_bytes));
+							last_emitted_line = Some(line_index);
+							after_remaining -= 1;
+						} else if before_context > 0 {
+							if before_buffer.len() == before_context { before_buffer.pop_front(); }
+							before_buffer.push_back((line_index, line_bytes.clone()));
 						}
+
+						line_index += 1;
 					}
 
 					// Entry iterator continuation upon no line captures found
@@ -184,7 +366,9 @@ fn main() {
 			};
 			
 			// Results display
-			if args.list {
+			if exec_active {
+				matched_paths.push(entry_path.to_path_buf());
+			} else if args.list {
 				entries_list.push(display_path);
 			} else {
 				println!("{display_path}");
@@ -195,8 +379,18 @@ fn main() {
 		}
 	}
 
-	// Listed entries display 
+	// Listed entries display
 	if entries_list.len() > 0 {
 		println!("{}", entries_list.join(" "));
 	}
+
+	// Command execution over matched entries
+	if let Some(exec_tokens) = args.exec {
+		let exit_code = exec::CommandTemplate::new(exec_tokens).run_for_each(matched_paths);
+		std::process::exit(exit_code);
+	}
+	if let Some(exec_batch_tokens) = args.exec_batch {
+		let exit_code = exec::CommandTemplate::new(exec_batch_tokens).run_batch(matched_paths);
+		std::process::exit(exit_code);
+	}
 }